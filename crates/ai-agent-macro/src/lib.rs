@@ -2,7 +2,7 @@ extern crate proc_macro;
 
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, Data, DeriveInput, Fields};
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr};
 
 // #[proc_macro_derive(KeywordString)]
 // pub fn print_keyword_derive(input: TokenStream) -> TokenStream {
@@ -67,3 +67,77 @@ pub fn print_keyword_derive(input: TokenStream) -> TokenStream {
 
     TokenStream::from(output)
 }
+
+#[proc_macro_derive(PromptContext, attributes(prompt_context))]
+pub fn prompt_context_derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let struct_name = &input.ident; // The name of the struct
+
+    // Group title, defaults to the struct name
+    let mut title = struct_name.to_string();
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("prompt_context"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("title") {
+                title = meta.value()?.parse::<LitStr>()?.value();
+                Ok(())
+            } else {
+                Err(meta.error("unsupported prompt_context attribute"))
+            }
+        })
+        .expect("Invalid prompt_context attribute");
+    }
+
+    // Each named field becomes a `key: value` line, except the skipped ones
+    let fields_tokens = if let Data::Struct(data) = input.data {
+        match data.fields {
+            Fields::Named(fields) => fields
+                .named
+                .into_iter()
+                .filter(|f| {
+                    let mut skip = false;
+                    for attr in f
+                        .attrs
+                        .iter()
+                        .filter(|attr| attr.path().is_ident("prompt_context"))
+                    {
+                        attr.parse_nested_meta(|meta| {
+                            if meta.path.is_ident("skip") {
+                                skip = true;
+                                Ok(())
+                            } else {
+                                Err(meta.error("unsupported prompt_context attribute"))
+                            }
+                        })
+                        .expect("Invalid prompt_context attribute");
+                    }
+                    !skip
+                })
+                .map(|f| {
+                    let field_ident = f.ident.expect("Expected named field");
+                    let field_name = field_ident.to_string();
+                    quote! { group.add_message(#field_name, &self.#field_ident.to_string()); }
+                })
+                .collect::<Vec<_>>(),
+            _ => panic!("PromptContext only supports structs with named fields"),
+        }
+    } else {
+        panic!("PromptContext can only be applied to structs");
+    };
+
+    let output = quote! {
+        impl ToPromptMessage for #struct_name {
+            fn to_prompt_message(&self) -> PromptMessage {
+                let mut group = PromptMessage::new_key_value(#title);
+                #(#fields_tokens)*
+                group
+            }
+        }
+    };
+
+    TokenStream::from(output)
+}
@@ -1,4 +1,4 @@
-use crate::{traits::LLMBackend, Error};
+use crate::{traits::LLMBackend, Error, Generation, GenerationConfig, TokenUsage};
 use serde::{Deserialize, Serialize};
 
 const CHATGPT_URL: &'static str = "https://api.openai.com/v1/chat/completions";
@@ -27,9 +27,21 @@ pub struct Message {
 
 #[derive(Debug, Serialize, Clone)]
 pub struct ChatCompletion {
+    // Azure selects the model by deployment in the url instead.
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub model: String,
     pub messages: Vec<Message>,
     pub temperature: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stop: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f64>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -55,44 +67,128 @@ pub struct Usage {
     pub total_tokens: u32,
 }
 
+#[derive(Debug)]
+enum Endpoint {
+    OpenAI,
+    Azure {
+        endpoint: String,
+        api_version: String,
+    },
+}
+
 #[derive(Debug)]
 pub struct ChatGpt {
     api_key: String,
+    // model name, or the deployment name on Azure
     model: String,
+    endpoint: Endpoint,
 }
 
 impl ChatGpt {
     pub fn new(api_key: String, model: String) -> Self {
-        Self { api_key, model }
+        Self {
+            api_key,
+            model,
+            endpoint: Endpoint::OpenAI,
+        }
     }
-}
 
-#[async_trait::async_trait]
-impl LLMBackend for ChatGpt {
-    async fn generate_response(&self, temperature: f64, prompt: &str) -> Result<String, Error> {
-        let chat_completion: ChatCompletion = ChatCompletion {
-            model: self.model.clone(),
+    pub fn new_azure(
+        api_key: String,
+        endpoint: String,
+        deployment: String,
+        api_version: String,
+    ) -> Self {
+        Self {
+            api_key,
+            model: deployment,
+            endpoint: Endpoint::Azure {
+                endpoint,
+                api_version,
+            },
+        }
+    }
+
+    fn url(&self) -> String {
+        match &self.endpoint {
+            Endpoint::OpenAI => CHATGPT_URL.to_string(),
+            Endpoint::Azure {
+                endpoint,
+                api_version,
+            } => format!(
+                "{}/openai/deployments/{}/chat/completions?api-version={}",
+                endpoint.trim_end_matches('/'),
+                self.model,
+                api_version
+            ),
+        }
+    }
+
+    fn chat_completion(&self, config: &GenerationConfig, prompt: &str) -> ChatCompletion {
+        ChatCompletion {
+            model: match self.endpoint {
+                Endpoint::OpenAI => self.model.clone(),
+                Endpoint::Azure { .. } => String::new(),
+            },
             messages: vec![Message {
                 role: Role::System,
                 content: prompt.to_string(),
                 name: None,
             }],
-            temperature,
-        };
+            temperature: config.temperature,
+            seed: config.seed,
+            max_tokens: config.max_output_tokens,
+            stop: config.stop.clone(),
+            top_p: config.top_p,
+            frequency_penalty: config.frequency_penalty,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl LLMBackend for ChatGpt {
+    fn describe(&self) -> String {
+        match self.endpoint {
+            Endpoint::OpenAI => format!("ChatGPT ({})", self.model),
+            Endpoint::Azure { .. } => format!("Azure OpenAI ({})", self.model),
+        }
+    }
+
+    async fn generate_response(
+        &self,
+        config: &GenerationConfig,
+        prompt: &str,
+    ) -> Result<Generation, Error> {
+        let chat_completion = self.chat_completion(config, prompt);
 
         let body = serde_json::to_string(&chat_completion).unwrap();
 
-        let mut request = ehttp::Request::post(CHATGPT_URL, body.into_bytes());
-        request
-            .headers
-            .insert("Authorization", &format!("Bearer {}", self.api_key));
+        let mut request = ehttp::Request::post(self.url(), body.into_bytes());
+        match self.endpoint {
+            Endpoint::OpenAI => request
+                .headers
+                .insert("Authorization", format!("Bearer {}", self.api_key)),
+            Endpoint::Azure { .. } => request.headers.insert("api-key", &self.api_key),
+        }
         request.headers.insert("Content-Type", "application/json");
         let response = ehttp::fetch_async(request)
             .await
             .map_err(|e| Error::RequestError(e))?;
         let result = String::from_utf8(response.bytes)?;
+        if !response.ok {
+            return Err(Error::ResponseError {
+                status: response.status,
+                message: result,
+            });
+        }
         let result = serde_json::from_str::<OpenAIChatResponse>(&result)?;
-        return Ok(result.choices[0].message.content.clone());
+        return Ok(Generation {
+            content: result.choices[0].message.content.clone(),
+            usage: Some(TokenUsage {
+                prompt_tokens: result.usage.prompt_tokens,
+                completion_tokens: result.usage.completion_tokens,
+            }),
+        });
 
         // let result: OpenAIChatResponse = ureq::post(CHATGPT_URL)
         //     .set("authorization", &format!("Bearer {}", self.api_key))
@@ -109,7 +205,53 @@ impl LLMBackend for ChatGpt {
 mod tests {
 
     use super::ChatGpt;
-    use crate::traits::LLMBackend;
+    use crate::{traits::LLMBackend, GenerationConfig};
+
+    #[test]
+    fn chat_completion_config() {
+        let gpt = ChatGpt::new("".into(), "gpt-3.5-turbo".into());
+
+        let body =
+            serde_json::to_value(gpt.chat_completion(&GenerationConfig::default(), "Hi")).unwrap();
+        assert!(body.get("seed").is_none());
+        assert!(body.get("max_tokens").is_none());
+        assert!(body.get("stop").is_none());
+
+        let config = GenerationConfig {
+            temperature: 0.0,
+            seed: Some(7),
+            max_output_tokens: Some(256),
+            stop: vec!["\n\n".into()],
+            top_p: Some(0.5),
+            frequency_penalty: Some(0.25),
+        };
+        let body = serde_json::to_value(gpt.chat_completion(&config, "Hi")).unwrap();
+        assert_eq!(body["seed"], 7);
+        assert_eq!(body["temperature"], 0.0);
+        assert_eq!(body["max_tokens"], 256);
+        assert_eq!(body["stop"][0], "\n\n");
+        assert_eq!(body["top_p"], 0.5);
+        assert_eq!(body["frequency_penalty"], 0.25);
+    }
+
+    #[test]
+    fn azure_chat_completion() {
+        let gpt = ChatGpt::new_azure(
+            "".into(),
+            "https://example.openai.azure.com/".into(),
+            "gpt-35".into(),
+            "2024-02-01".into(),
+        );
+        assert_eq!(
+            gpt.url(),
+            "https://example.openai.azure.com/openai/deployments/gpt-35/chat/completions?api-version=2024-02-01"
+        );
+
+        let body =
+            serde_json::to_value(gpt.chat_completion(&GenerationConfig::default(), "Hi")).unwrap();
+        assert!(body.get("model").is_none());
+        assert_eq!(gpt.describe(), "Azure OpenAI (gpt-35)");
+    }
 
     #[ignore]
     #[test]
@@ -121,11 +263,17 @@ mod tests {
                 std::env::var("OPEN_API_KEY").unwrap(),
                 "gpt-3.5-turbo".into(),
             );
-            let result = gpt.generate_response(0.1, "Just Say only 'Hello'").await;
+            let config = GenerationConfig {
+                temperature: 0.1,
+                ..Default::default()
+            };
+            let result = gpt
+                .generate_response(&config, "Just Say only 'Hello'")
+                .await;
             // println!("{:?}", result);
 
             assert!(result.is_ok());
-            assert_eq!(result.unwrap(), "Hello");
+            assert_eq!(result.unwrap().content, "Hello");
         });
     }
 }
@@ -0,0 +1,227 @@
+use crate::{traits::LLMBackend, Error, Generation, GenerationConfig, TokenUsage};
+use serde::{Deserialize, Serialize};
+
+const GEMINI_URL: &str = "https://generativelanguage.googleapis.com/v1beta/models";
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct Part {
+    #[serde(default)]
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct Content {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    #[serde(default)]
+    pub parts: Vec<Part>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiGenerationConfig {
+    pub temperature: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stop_sequences: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f64>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateContentRequest {
+    pub contents: Vec<Content>,
+    pub generation_config: GeminiGenerationConfig,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateContentResponse {
+    #[serde(default)]
+    pub candidates: Vec<Candidate>,
+    pub usage_metadata: Option<UsageMetadata>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageMetadata {
+    #[serde(default)]
+    pub prompt_token_count: u32,
+    // left out when the response is blocked
+    #[serde(default)]
+    pub candidates_token_count: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Candidate {
+    #[serde(default)]
+    pub content: Content,
+    pub finish_reason: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Gemini {
+    api_key: String,
+    model: String,
+}
+
+impl Gemini {
+    pub fn new(api_key: String, model: String) -> Self {
+        Self { api_key, model }
+    }
+
+    fn url(&self) -> String {
+        format!("{}/{}:generateContent", GEMINI_URL, self.model)
+    }
+
+    fn generate_content(&self, config: &GenerationConfig, prompt: &str) -> GenerateContentRequest {
+        GenerateContentRequest {
+            // Unlike ChatGPT's system message, Gemini needs at least one `contents` entry and
+            // `systemInstruction` alone is rejected, so the single rendered prompt is the user turn.
+            contents: vec![Content {
+                role: Some("user".into()),
+                parts: vec![Part {
+                    text: prompt.to_string(),
+                }],
+            }],
+            generation_config: GeminiGenerationConfig {
+                temperature: config.temperature,
+                seed: config.seed,
+                max_output_tokens: config.max_output_tokens,
+                stop_sequences: config.stop.clone(),
+                top_p: config.top_p,
+                frequency_penalty: config.frequency_penalty,
+            },
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl LLMBackend for Gemini {
+    fn describe(&self) -> String {
+        format!("Gemini ({})", self.model)
+    }
+
+    async fn generate_response(
+        &self,
+        config: &GenerationConfig,
+        prompt: &str,
+    ) -> Result<Generation, Error> {
+        let body = serde_json::to_string(&self.generate_content(config, prompt))?;
+
+        let mut request = ehttp::Request::post(self.url(), body.into_bytes());
+        request.headers.insert("x-goog-api-key", &self.api_key);
+        request.headers.insert("Content-Type", "application/json");
+        let response = ehttp::fetch_async(request)
+            .await
+            .map_err(Error::RequestError)?;
+        let result = String::from_utf8(response.bytes)?;
+        if !response.ok {
+            return Err(Error::ResponseError {
+                status: response.status,
+                message: result,
+            });
+        }
+
+        let result = serde_json::from_str::<GenerateContentResponse>(&result)?;
+        let usage = result.usage_metadata.map(|usage| TokenUsage {
+            prompt_tokens: usage.prompt_token_count,
+            completion_tokens: usage.candidates_token_count,
+        });
+        let candidate =
+            result
+                .candidates
+                .into_iter()
+                .next()
+                .ok_or_else(|| Error::ResponseError {
+                    status: response.status,
+                    message: "No candidates in response".into(),
+                })?;
+        Ok(Generation {
+            content: candidate
+                .content
+                .parts
+                .into_iter()
+                .map(|part| part.text)
+                .collect::<Vec<String>>()
+                .join(""),
+            usage,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{Gemini, GenerateContentResponse};
+    use crate::{traits::LLMBackend, GenerationConfig};
+
+    #[test]
+    fn generate_content_request() {
+        let gemini = Gemini::new("".into(), "gemini-1.5-flash".into());
+        assert_eq!(
+            gemini.url(),
+            "https://generativelanguage.googleapis.com/v1beta/models/gemini-1.5-flash:generateContent"
+        );
+
+        let config = GenerationConfig {
+            temperature: 0.5,
+            seed: Some(7),
+            max_output_tokens: Some(256),
+            stop: vec!["END".into()],
+            ..Default::default()
+        };
+        let body = serde_json::to_value(gemini.generate_content(&config, "Hi")).unwrap();
+        assert_eq!(body["contents"][0]["role"], "user");
+        assert_eq!(body["contents"][0]["parts"][0]["text"], "Hi");
+        assert_eq!(body["generationConfig"]["temperature"], 0.5);
+        assert_eq!(body["generationConfig"]["seed"], 7);
+        assert_eq!(body["generationConfig"]["maxOutputTokens"], 256);
+        assert_eq!(body["generationConfig"]["stopSequences"][0], "END");
+        assert!(body["generationConfig"].get("topP").is_none());
+    }
+
+    #[test]
+    fn generate_content_response() {
+        let response = serde_json::from_str::<GenerateContentResponse>(
+            r#"{"candidates": [{"content": {"role": "model", "parts": [{"text": "Hel"}, {"text": "lo"}]}, "finishReason": "STOP"}], "usageMetadata": {"promptTokenCount": 4, "candidatesTokenCount": 2, "totalTokenCount": 6}}"#,
+        )
+        .unwrap();
+        let candidate = &response.candidates[0];
+        assert_eq!(candidate.finish_reason.as_deref(), Some("STOP"));
+        assert_eq!(candidate.content.parts.len(), 2);
+        let usage = response.usage_metadata.unwrap();
+        assert_eq!(usage.prompt_token_count, 4);
+        assert_eq!(usage.candidates_token_count, 2);
+    }
+
+    #[ignore]
+    #[test]
+    fn calling_gemini() {
+        dotenv::dotenv().ok();
+
+        smol::block_on(async {
+            let gemini = Gemini::new(
+                std::env::var("GEMINI_API_KEY").unwrap(),
+                "gemini-1.5-flash".into(),
+            );
+            let config = GenerationConfig {
+                temperature: 0.1,
+                ..Default::default()
+            };
+            let result = gemini
+                .generate_response(&config, "Just Say only 'Hello'")
+                .await;
+
+            assert!(result.is_ok());
+            assert_eq!(result.unwrap().content.trim(), "Hello");
+        });
+    }
+}
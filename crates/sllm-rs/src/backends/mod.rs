@@ -1,8 +1,21 @@
 mod chatgpt;
+mod gemini;
 use crate::{traits::LLMBackend, Backend, Error};
 
 pub fn create_llm_model(config: Backend) -> Result<Box<dyn LLMBackend>, Error> {
     match config {
         Backend::ChatGPT { api_key, model } => Ok(Box::new(chatgpt::ChatGpt::new(api_key, model))),
+        Backend::AzureOpenAI {
+            api_key,
+            endpoint,
+            deployment,
+            api_version,
+        } => Ok(Box::new(chatgpt::ChatGpt::new_azure(
+            api_key,
+            endpoint,
+            deployment,
+            api_version,
+        ))),
+        Backend::Gemini { api_key, model } => Ok(Box::new(gemini::Gemini::new(api_key, model))),
     }
 }
@@ -9,9 +9,28 @@ pub enum Error {
 
     #[error("Request error {0}")]
     RequestError(String),
+    #[error("Response error {status}: {message}")]
+    ResponseError { status: u16, message: String },
     #[error(transparent)]
     TeraError(#[from] tera::Error),
+    #[error("Invalid template variables - missing: {missing:?}, unknown: {unknown:?}, mismatched: {mismatched:?}")]
+    TemplateVariableError {
+        missing: Vec<String>,
+        unknown: Vec<String>,
+        mismatched: Vec<String>,
+    },
     #[error(transparent)]
     JSONParsingError(#[from] serde_json::Error), // #[error(transparent)]
                                                  // RequestError(#[from] ureq::Error),
 }
+
+impl Error {
+    // Errors worth retrying: network failures, rate limits and server errors.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::IOError(_) | Self::RequestError(_) => true,
+            Self::ResponseError { status, .. } => *status == 429 || *status >= 500,
+            _ => false,
+        }
+    }
+}
@@ -12,18 +12,71 @@ use traits::{LLMBackend, MessageBuilder};
 //
 //
 pub enum Backend {
-    ChatGPT { api_key: String, model: String },
+    ChatGPT {
+        api_key: String,
+        model: String,
+    },
+    // e.g. endpoint: "https://{resource}.openai.azure.com", api_version: "2024-02-01"
+    AzureOpenAI {
+        api_key: String,
+        endpoint: String,
+        deployment: String,
+        api_version: String,
+    },
+    Gemini {
+        api_key: String,
+        model: String,
+    },
     // TODO Future beackends
     // Llama2Cpu { path: String },
 }
 
 impl Backend {}
 
+// Sampling parameters sent with each request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenerationConfig {
+    pub temperature: f64,
+    // Honored by backends that support seeded sampling.
+    pub seed: Option<u64>,
+    pub max_output_tokens: Option<u32>,
+    pub stop: Vec<String>,
+    pub top_p: Option<f64>,
+    pub frequency_penalty: Option<f64>,
+}
+
+impl Default for GenerationConfig {
+    fn default() -> Self {
+        Self {
+            temperature: 0.9,
+            seed: None,
+            max_output_tokens: None,
+            stop: Vec::new(),
+            top_p: None,
+            frequency_penalty: None,
+        }
+    }
+}
+
+// Tokens reported by the backend for one request.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenUsage {
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+}
+
+// A response, with its token usage when the backend reports it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Generation {
+    pub content: String,
+    pub usage: Option<TokenUsage>,
+}
+
 // manage the messages?
 #[derive(Debug)]
 pub struct Model {
     backend: Box<dyn LLMBackend>,
-    temperature: f64,
+    config: GenerationConfig,
 }
 
 impl Model {
@@ -31,7 +84,7 @@ impl Model {
         let backend = create_llm_model(config)?;
         Ok(Self {
             backend,
-            temperature: 0.9,
+            config: GenerationConfig::default(),
         })
     }
 
@@ -40,22 +93,62 @@ impl Model {
         T: IntoIterator + Send,
         T::Item: MessageBuilder + Send,
     {
-        self.backend
-            .generate_response(
-                self.temperature,
-                PromptMessageBuilder::new(context_message_group)
-                    .build()
-                    .as_str(),
-            )
-            .await
+        Ok(self
+            .generate_with_usage(context_message_group)
+            .await?
+            .content)
+    }
+
+    pub async fn generate_with_usage<T>(
+        &self,
+        context_message_group: T,
+    ) -> Result<Generation, Error>
+    where
+        T: IntoIterator + Send,
+        T::Item: MessageBuilder + Send,
+    {
+        let prompt = PromptMessageBuilder::new(context_message_group).try_build()?;
+        self.backend.generate_response(&self.config, &prompt).await
+    }
+
+    // Swap the backend, e.g. to rotate keys, keeping the generation config.
+    pub fn set_backend(&mut self, config: Backend) -> Result<(), Error> {
+        self.backend = create_llm_model(config)?;
+        Ok(())
+    }
+
+    pub fn describe(&self) -> String {
+        self.backend.describe()
+    }
+
+    pub fn set_generation_config(&mut self, config: GenerationConfig) {
+        self.config = config;
+    }
+
+    pub fn generation_config(&self) -> &GenerationConfig {
+        &self.config
     }
 
     pub fn set_temperature(&mut self, temperature: f64) {
-        self.temperature = temperature;
+        self.config.temperature = temperature;
     }
 
     pub fn temperature(&self) -> f64 {
-        self.temperature
+        self.config.temperature
+    }
+
+    pub fn set_seed(&mut self, seed: Option<u64>) {
+        self.config.seed = seed;
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.config.seed
+    }
+
+    // Pin sampling so repeated requests give reproducible output where the backend allows it.
+    pub fn set_deterministic(&mut self, seed: u64) {
+        self.config.temperature = 0.0;
+        self.config.seed = Some(seed);
     }
 }
 
@@ -75,4 +168,20 @@ mod tests {
         })
         .is_ok());
     }
+
+    #[test]
+    fn test_error_transient() {
+        let error = |status| Error::ResponseError {
+            status,
+            message: "".into(),
+        };
+        assert!(error(429).is_transient());
+        assert!(error(503).is_transient());
+        assert!(!error(401).is_transient());
+        assert!(Error::RequestError("connection reset".into()).is_transient());
+        assert!(!serde_json::from_str::<u32>("")
+            .map_err(Error::from)
+            .unwrap_err()
+            .is_transient());
+    }
 }
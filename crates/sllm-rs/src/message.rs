@@ -1,14 +1,45 @@
 use std::{fmt, sync::Arc};
 
 use serde::Serialize;
-use tera::{Context, Tera};
+use tera::{Context, Tera, Value};
 
 pub use crate::traits::MessageBuilder;
+use crate::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariableType {
+    String,
+    Number,
+    Bool,
+    Array,
+    Object,
+}
+
+impl VariableType {
+    fn matches(&self, value: &Value) -> bool {
+        match self {
+            Self::String => value.is_string(),
+            Self::Number => value.is_number(),
+            Self::Bool => value.is_boolean(),
+            Self::Array => value.is_array(),
+            Self::Object => value.is_object(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct VariableDecl {
+    name: String,
+    ty: VariableType,
+    required: bool,
+}
 
 #[derive(Clone)]
 pub struct TemplatedMessage {
     template: String,
     context: Context,
+    partials: Vec<(String, String)>,
+    variables: Vec<VariableDecl>,
 }
 
 impl TemplatedMessage {
@@ -16,9 +47,76 @@ impl TemplatedMessage {
         Self {
             template: template.to_string(),
             context: Context::new(),
+            partials: Vec::new(),
+            variables: Vec::new(),
         }
     }
 
+    // Declare an expected variable. Once any is declared, `validate` also rejects undeclared ones.
+    pub fn declare_variable(&mut self, name: &str, ty: VariableType, required: bool) {
+        self.variables.push(VariableDecl {
+            name: name.into(),
+            ty,
+            required,
+        });
+    }
+
+    // Check the inserted values against the declared variables.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.variables.is_empty() {
+            return Ok(());
+        }
+
+        let mut missing = Vec::new();
+        let mut mismatched = Vec::new();
+        for decl in self.variables.iter() {
+            match self.context.get(&decl.name) {
+                Some(value) if !decl.ty.matches(value) => {
+                    mismatched.push(format!("{} (expected {:?})", decl.name, decl.ty))
+                }
+                Some(_) => {}
+                None if decl.required => missing.push(decl.name.clone()),
+                None => {}
+            }
+        }
+
+        let mut unknown = match self.context.clone().into_json() {
+            Value::Object(values) => values
+                .keys()
+                .filter(|key| !self.variables.iter().any(|decl| &decl.name == *key))
+                .cloned()
+                .collect::<Vec<String>>(),
+            _ => Vec::new(),
+        };
+        unknown.sort();
+
+        if missing.is_empty() && unknown.is_empty() && mismatched.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::TemplateVariableError {
+                missing,
+                unknown,
+                mismatched,
+            })
+        }
+    }
+
+    // Register a named fragment usable with `{% include "name" %}` or `{% extends "name" %}`.
+    pub fn add_partial(&mut self, name: &str, template: &str) {
+        self.partials.push((name.into(), template.into()));
+    }
+
+    fn render(&self) -> Result<String, Error> {
+        let mut tera = Tera::default();
+        tera.add_raw_templates(
+            self.partials
+                .iter()
+                .map(|(name, partial)| (name.as_str(), partial.as_str()))
+                .chain(std::iter::once(("template", self.template.as_str()))),
+        )?;
+        Ok(tera.render("template", &self.context)?)
+    }
+
     pub fn insert<T: Serialize + ?Sized, S: Into<String>>(&mut self, key: S, val: &T) {
         self.context.insert(key, val);
     }
@@ -82,6 +180,14 @@ impl std::fmt::Debug for PromptMessage {
                 .debug_struct("Templated")
                 .field("template", &templated_msg.template)
                 .field("context", &templated_msg.context)
+                .field(
+                    "partials",
+                    &templated_msg
+                        .partials
+                        .iter()
+                        .map(|(name, _)| name.clone())
+                        .collect::<Vec<String>>(),
+                )
                 .finish(),
             PromptMessage::Simple(message) => f.debug_tuple("Simple").field(message).finish(),
         }
@@ -152,13 +258,18 @@ impl MessageBuilder for PromptMessage {
                     format!("[{}]\n{}", title, rendered_messages)
                 }
             }
+            PromptMessage::Templated(templated_msg) => templated_msg.render().unwrap(),
+            PromptMessage::Simple(message) => message.clone(),
+        }
+    }
+
+    fn try_build(&mut self) -> Result<String, Error> {
+        match self {
             PromptMessage::Templated(templated_msg) => {
-                let mut tera = Tera::default();
-                tera.add_raw_template("template", &templated_msg.template)
-                    .unwrap();
-                tera.render("template", &templated_msg.context).unwrap()
+                templated_msg.validate()?;
+                templated_msg.render()
             }
-            PromptMessage::Simple(message) => message.clone(),
+            _ => Ok(self.build()),
         }
     }
 }
@@ -202,6 +313,19 @@ where
             .collect::<Vec<String>>()
             .join("\n\n")
     }
+
+    fn try_build(&mut self) -> Result<String, Error> {
+        let groups = self
+            .groups
+            .take()
+            .expect("Groups should not be taken more than once");
+
+        Ok(groups
+            .into_iter()
+            .map(|mut group| group.try_build())
+            .collect::<Result<Vec<String>, Error>>()?
+            .join("\n\n"))
+    }
 }
 
 #[cfg(test)]
@@ -229,6 +353,70 @@ mod tests {
         assert_eq!(output, expected_output);
     }
 
+    #[test]
+    fn test_templated_partial_build() {
+        let mut msg = TemplatedMessage::new(
+            "{% extends \"base\" %}{% block body %}Hello, {{ name }}!{% endblock body %}",
+        );
+        msg.add_partial(
+            "base",
+            "{% block body %}{% endblock body %}\n{% include \"disclaimer\" %}",
+        );
+        msg.add_partial("disclaimer", "Answers may be inaccurate.");
+        msg.insert("name", "World");
+        let mut group = PromptMessage::new_templated(msg);
+        let output = group.build();
+        let expected_output = "Hello, World!\nAnswers may be inaccurate.";
+        assert_eq!(output, expected_output);
+    }
+
+    #[test]
+    fn test_templated_validate() {
+        let mut msg = TemplatedMessage::new("{{ agent_name }} handles {{ num_orders }} orders.");
+        msg.declare_variable("agent_name", VariableType::String, true);
+        msg.declare_variable("num_orders", VariableType::Number, true);
+        msg.declare_variable("tone", VariableType::String, false);
+        msg.insert("num_orders", "many");
+        msg.insert("language", "en");
+
+        let Err(Error::TemplateVariableError {
+            missing,
+            unknown,
+            mismatched,
+        }) = msg.validate()
+        else {
+            panic!("validation should fail");
+        };
+        assert_eq!(missing, vec!["agent_name"]);
+        assert_eq!(unknown, vec!["language"]);
+        assert_eq!(mismatched, vec!["num_orders (expected Number)"]);
+
+        msg.insert("agent_name", "Assistant");
+        msg.insert("num_orders", &3);
+        msg.remove("language");
+        assert!(msg.validate().is_ok());
+    }
+
+    #[test]
+    fn test_templated_try_build() {
+        let mut msg = TemplatedMessage::new("Hello, {{ name }}!");
+        msg.declare_variable("name", VariableType::String, true);
+        let mut group = PromptMessage::new_templated(msg.clone());
+        assert!(matches!(
+            group.try_build(),
+            Err(Error::TemplateVariableError { .. })
+        ));
+
+        // undeclared variables fail at render time instead
+        let mut group = PromptMessage::new_templated(TemplatedMessage::new("Hello, {{ name }}!"));
+        assert!(matches!(group.try_build(), Err(Error::TeraError(_))));
+
+        msg.insert("name", "World");
+        let mut builder =
+            PromptMessageBuilder::new(vec![PromptMessage::new_templated(msg), "Bye.".into()]);
+        assert_eq!(builder.try_build().unwrap(), "Hello, World!\n\nBye.");
+    }
+
     #[test]
     fn test_simple_build() {
         let mut group = PromptMessage::new_simple("Just a simple message.".to_string());
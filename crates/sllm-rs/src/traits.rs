@@ -1,11 +1,23 @@
-use crate::error::Error;
+use crate::{error::Error, Generation, GenerationConfig};
 use async_trait::async_trait;
 
 #[async_trait]
 pub trait LLMBackend: std::fmt::Debug + Send + Sync {
-    async fn generate_response(&self, temperature: f64, prompt: &str) -> Result<String, Error>;
+    // Backend and model name, without credentials.
+    fn describe(&self) -> String;
+
+    async fn generate_response(
+        &self,
+        config: &GenerationConfig,
+        prompt: &str,
+    ) -> Result<Generation, Error>;
 }
 
 pub trait MessageBuilder {
     fn build(&mut self) -> String;
+
+    // Like `build`, but returns template errors instead of panicking.
+    fn try_build(&mut self) -> Result<String, Error> {
+        Ok(self.build())
+    }
 }
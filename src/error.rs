@@ -8,8 +8,100 @@ pub enum Error {
     InputRequiredError,
     #[error("{0} not found.")]
     NotFound(String),
+    #[error("Request timed out")]
+    Timeout,
     #[error(transparent)]
     SLLMError(#[from] sllm::Error),
     #[error(transparent)]
     JsonParsingError(#[from] serde_json::Error),
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCategory {
+    // The LLM backend failed or could not be reached
+    Provider,
+    // A prompt template failed to render or validate
+    Template,
+    // A response could not be parsed
+    Parse,
+    Input,
+    Output,
+    // A node or group is missing from the pipeline
+    Pipeline,
+}
+
+impl Error {
+    // Errors worth retrying, see `RetryPolicy`.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Timeout => true,
+            Self::SLLMError(e) => e.is_transient(),
+            _ => false,
+        }
+    }
+
+    // Stable machine-readable code, unaffected by changes to the message.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::WrongOutputType => "OUTPUT_WRONG_TYPE",
+            Self::OutputIsEmpty => "OUTPUT_EMPTY",
+            Self::InputRequiredError => "INPUT_REQUIRED",
+            Self::NotFound(_) => "PIPELINE_NOT_FOUND",
+            Self::Timeout => "PROVIDER_TIMEOUT",
+            Self::SLLMError(e) => match e {
+                sllm::Error::IOError(_) | sllm::Error::RequestError(_) => "PROVIDER_REQUEST",
+                sllm::Error::ResponseError { status: 429, .. } => "PROVIDER_RATE_LIMITED",
+                sllm::Error::ResponseError { .. } => "PROVIDER_RESPONSE",
+                sllm::Error::Utf8Error(_) | sllm::Error::JSONParsingError(_) => {
+                    "PROVIDER_MALFORMED_RESPONSE"
+                }
+                sllm::Error::TeraError(_) => "TEMPLATE_RENDER",
+                sllm::Error::TemplateVariableError { .. } => "TEMPLATE_VARIABLES",
+            },
+            Self::JsonParsingError(_) => "PARSE_JSON",
+        }
+    }
+
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            Self::WrongOutputType | Self::OutputIsEmpty => ErrorCategory::Output,
+            Self::InputRequiredError => ErrorCategory::Input,
+            Self::NotFound(_) => ErrorCategory::Pipeline,
+            Self::Timeout => ErrorCategory::Provider,
+            Self::SLLMError(e) => match e {
+                sllm::Error::TeraError(_) | sllm::Error::TemplateVariableError { .. } => {
+                    ErrorCategory::Template
+                }
+                _ => ErrorCategory::Provider,
+            },
+            Self::JsonParsingError(_) => ErrorCategory::Parse,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, ErrorCategory};
+
+    #[test]
+    fn test_error_code() {
+        let error = Error::from(sllm::Error::ResponseError {
+            status: 429,
+            message: "".into(),
+        });
+        assert_eq!(error.code(), "PROVIDER_RATE_LIMITED");
+        assert_eq!(error.category(), ErrorCategory::Provider);
+
+        let error = Error::from(sllm::Error::TemplateVariableError {
+            missing: vec!["agent_name".into()],
+            unknown: vec![],
+            mismatched: vec![],
+        });
+        assert_eq!(error.code(), "TEMPLATE_VARIABLES");
+        assert_eq!(error.category(), ErrorCategory::Template);
+
+        let error = Error::NotFound("group1".into());
+        assert_eq!(error.code(), "PIPELINE_NOT_FOUND");
+        assert_eq!(error.category(), ErrorCategory::Pipeline);
+    }
+}
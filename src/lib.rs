@@ -1,6 +1,10 @@
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use sllm::message::{MessageBuilder, PromptMessage};
+use futures::StreamExt;
+use sllm::message::{MessageBuilder, PromptMessage, PromptMessageBuilder};
 
 pub mod sync;
 pub mod units;
@@ -8,22 +12,29 @@ pub mod units;
 mod error;
 mod pipeline_net;
 mod prompt_manager;
+mod stats;
 mod traits;
 
-pub use error::Error;
+pub use error::{Error, ErrorCategory};
 pub use pipeline_net::PipelineNet;
 pub use prompt_manager::PromptManager;
-pub use sllm::Backend;
+pub use sllm::{Backend, GenerationConfig};
+pub use stats::ModelStats;
 pub use traits::*;
 
 pub trait ToKeywordString {
     fn to_keyword_string() -> String;
 }
 
+// Render a value as a `[title]` key-value group. Derive with `PromptContext`.
+pub trait ToPromptMessage {
+    fn to_prompt_message(&self) -> PromptMessage;
+}
+
 pub mod prelude {
-    pub use super::ToKeywordString;
+    pub use super::{ToKeywordString, ToPromptMessage};
     pub use ai_agent_macro::*;
-    pub use sllm::message::{MessageBuilder, PromptMessage, TemplatedMessage};
+    pub use sllm::message::{MessageBuilder, PromptMessage, TemplatedMessage, VariableType};
 }
 
 #[derive(Debug, Clone)]
@@ -94,34 +105,173 @@ impl From<String> for ModuleParam {
     }
 }
 
+// Retry transient errors, waiting `backoff` and doubling it after each attempt.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub backoff: Duration,
+}
+
 //
 // Model Wrapper
 #[derive(Debug, Clone)]
 pub struct Model {
-    model: Arc<sync::Mutex<sllm::Model>>,
+    model: Arc<sync::RwLock<sllm::Model>>,
+    timeout: Option<Duration>,
+    retry_policy: Option<RetryPolicy>,
+    stats: Arc<sync::Mutex<stats::StatsRecorder>>,
 }
 
 impl Model {
     pub fn new(backend: Backend) -> Result<Self, Error> {
         let model = sllm::Model::new(backend)?;
         Ok(Self {
-            model: Arc::new(sync::Mutex::new(model)),
+            model: Arc::new(sync::RwLock::new(model)),
+            timeout: None,
+            retry_policy: None,
+            stats: Arc::new(sync::Mutex::new(stats::StatsRecorder::default())),
         })
     }
 
+    // Replace the backend for every clone. Calls already running finish on the old one.
+    pub async fn set_backend(&self, backend: Backend) -> Result<(), Error> {
+        let mut model = self.model.write().await;
+        model.set_backend(backend)?;
+        Ok(())
+    }
+
+    pub async fn set_generation_config(&self, config: GenerationConfig) {
+        let mut model = self.model.write().await;
+        model.set_generation_config(config);
+    }
+
+    pub async fn generation_config(&self) -> GenerationConfig {
+        self.model.read().await.generation_config().clone()
+    }
+
     pub async fn set_temperature(&self, temperature: f64) {
-        let mut model = self.model.lock().await;
+        let mut model = self.model.write().await;
         model.set_temperature(temperature);
     }
 
+    pub async fn set_seed(&self, seed: Option<u64>) {
+        let mut model = self.model.write().await;
+        model.set_seed(seed);
+    }
+
+    pub async fn set_deterministic(&self, seed: u64) {
+        let mut model = self.model.write().await;
+        model.set_deterministic(seed);
+    }
+
+    // Limit each call to the backend. `None` waits until the backend responds.
+    // Unlike the backend and generation config, this applies to this clone only.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    // Like the timeout, this applies to this clone only.
+    pub fn set_retry_policy(&mut self, retry_policy: Option<RetryPolicy>) {
+        self.retry_policy = retry_policy;
+    }
+
+    pub fn retry_policy(&self) -> Option<RetryPolicy> {
+        self.retry_policy
+    }
+
+    // Call counts and latencies shared by every clone of this model.
+    pub async fn stats(&self) -> ModelStats {
+        self.stats.lock().await.snapshot()
+    }
+
+    // Backend and model name, without credentials.
+    pub async fn describe(&self) -> String {
+        self.model.read().await.describe()
+    }
+
     pub async fn generate_response<T>(&self, input: T) -> Result<String, Error>
     where
         T: IntoIterator + Send,
         T::Item: MessageBuilder + Send,
     {
-        let model = self.model.lock().await;
-        let result = model.generate_response(input).await?;
-        Ok(result)
+        // build once so the same prompt can be resent on retry
+        let prompt = PromptMessageBuilder::new(input).try_build()?;
+
+        let mut attempt = 0;
+        loop {
+            match self.generate_once(&prompt).await {
+                Err(e) if e.is_transient() => match self.retry_policy {
+                    Some(policy) if attempt < policy.max_retries => {
+                        let delay = policy.backoff * 2u32.saturating_pow(attempt);
+                        attempt += 1;
+                        self.stats.lock().await.record_retry();
+                        log::warn!(
+                            "retrying in {:?} ({}/{}) - {}",
+                            delay,
+                            attempt,
+                            policy.max_retries,
+                            e
+                        );
+                        sync::Timer::after(delay).await;
+                    }
+                    _ => return Err(e),
+                },
+                result => return result,
+            }
+        }
+    }
+
+    // Generate a response for each input, running up to `concurrency` calls at once.
+    // Results keep the order of the inputs.
+    pub async fn generate_batch<T>(
+        &self,
+        inputs: Vec<T>,
+        concurrency: usize,
+    ) -> Vec<Result<String, Error>>
+    where
+        T: IntoIterator + Send,
+        T::Item: MessageBuilder + Send,
+    {
+        futures::stream::iter(inputs)
+            .map(|input| self.generate_response(input))
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    // Locks the model per attempt so `set_backend` can swap it between retries.
+    async fn generate_once(&self, prompt: &str) -> Result<String, Error> {
+        let model = self.model.read().await;
+        let input = [PromptMessage::new_simple(prompt.into())];
+        let started = Instant::now();
+        let result = match self.timeout {
+            Some(timeout) => {
+                // the timer is polled first so an expired budget always wins
+                sync::or(
+                    async {
+                        sync::Timer::after(timeout).await;
+                        Err(Error::Timeout)
+                    },
+                    async { model.generate_with_usage(input).await.map_err(Error::from) },
+                )
+                .await
+            }
+            None => model.generate_with_usage(input).await.map_err(Error::from),
+        };
+        drop(model);
+        let mut stats = self.stats.lock().await;
+        stats.record_call(started.elapsed(), result.is_ok());
+        if let Ok(sllm::Generation {
+            usage: Some(usage), ..
+        }) = &result
+        {
+            stats.record_usage(*usage);
+        }
+        result.map(|generation| generation.content)
     }
 }
 
@@ -140,8 +290,223 @@ mod tests {
         .unwrap()
     }
 
-    use super::ToKeywordString;
-    use ai_agent_macro::KeywordString;
+    // a model whose every request times out without reaching the network
+    pub fn offline_model() -> Model {
+        let mut model = Model::new(sllm::Backend::ChatGPT {
+            api_key: "".into(),
+            model: "gpt-3.5-turbo".into(),
+        })
+        .unwrap();
+        model.set_timeout(Some(std::time::Duration::ZERO));
+        model
+    }
+
+    // An Azure OpenAI backend served from a local port, answering each request with
+    // the next `(status, body)`. The last one repeats once the rest are used up.
+    pub fn local_backend(responses: Vec<(u16, String)>) -> sllm::Backend {
+        use std::io::{BufRead, BufReader, Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for (index, stream) in listener.incoming().enumerate() {
+                let Ok(mut stream) = stream else { break };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut content_length = 0;
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap_or(0) > 0 && line != "\r\n" {
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap_or(0);
+                        }
+                    }
+                    line.clear();
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).ok();
+
+                let (status, body) = &responses[index.min(responses.len() - 1)];
+                write!(
+                    stream,
+                    "HTTP/1.1 {} Status\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .ok();
+            }
+        });
+
+        sllm::Backend::AzureOpenAI {
+            api_key: "".into(),
+            endpoint,
+            deployment: "local".into(),
+            api_version: "2024-02-01".into(),
+        }
+    }
+
+    pub fn chat_response(content: &str) -> String {
+        serde_json::json!({
+            "id": "chatcmpl-local",
+            "object": "chat.completion",
+            "created": 0,
+            "choices": [{
+                "index": 0,
+                "message": { "role": "assistant", "content": content },
+                "finish_reason": "stop"
+            }],
+            "usage": { "prompt_tokens": 9, "completion_tokens": 2, "total_tokens": 11 }
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_model_usage() {
+        let model = Model::new(local_backend(vec![(200, chat_response("Hello"))])).unwrap();
+
+        for _ in 0..2 {
+            let result = crate::sync::block_on(model.generate_response(vec![
+                sllm::message::PromptMessage::new_simple("Just Say only 'Hello'".into()),
+            ]));
+            assert_eq!(result.unwrap(), "Hello");
+        }
+
+        let stats = crate::sync::block_on(model.stats());
+        assert_eq!(stats.calls, 2);
+        assert_eq!(stats.errors, 0);
+        assert_eq!(stats.prompt_tokens, 18);
+        assert_eq!(stats.completion_tokens, 4);
+    }
+
+    #[test]
+    fn test_model_timeout() {
+        let model = offline_model();
+        let mut other = model.clone();
+        other.set_timeout(None);
+        assert_eq!(model.timeout(), Some(std::time::Duration::ZERO));
+
+        let result = crate::sync::block_on(model.generate_response(vec![
+            sllm::message::PromptMessage::new_simple("Just Say only 'Hello'".into()),
+        ]));
+        assert!(matches!(result, Err(crate::Error::Timeout)));
+    }
+
+    #[test]
+    fn test_model_template_error() {
+        let model = offline_model();
+        let mut msg = sllm::message::TemplatedMessage::new("Hello, {{ name }}!");
+        msg.declare_variable("name", sllm::message::VariableType::String, true);
+
+        let result = crate::sync::block_on(
+            model.generate_response(vec![sllm::message::PromptMessage::from(msg)]),
+        );
+        assert!(matches!(
+            result,
+            Err(crate::Error::SLLMError(
+                sllm::Error::TemplateVariableError { .. }
+            ))
+        ));
+        assert_eq!(crate::sync::block_on(model.stats()).calls, 0);
+    }
+
+    #[test]
+    fn test_model_retry() {
+        let mut model = offline_model();
+        model.set_retry_policy(Some(super::RetryPolicy {
+            max_retries: 2,
+            backoff: std::time::Duration::from_millis(1),
+        }));
+
+        let result = crate::sync::block_on(model.generate_response(vec![
+            sllm::message::PromptMessage::new_simple("Just Say only 'Hello'".into()),
+        ]));
+        assert!(matches!(result, Err(crate::Error::Timeout)));
+        assert!(result.unwrap_err().is_transient());
+
+        let stats = crate::sync::block_on(model.stats());
+        assert_eq!(stats.calls, 3);
+        assert_eq!(stats.errors, 3);
+        assert_eq!(stats.retries, 2);
+        assert_eq!(
+            crate::sync::block_on(model.describe()),
+            "ChatGPT (gpt-3.5-turbo)"
+        );
+    }
+
+    #[test]
+    fn test_model_batch() {
+        let model = offline_model();
+
+        let inputs = ["One", "Two", "Three"]
+            .into_iter()
+            .map(|text| vec![sllm::message::PromptMessage::new_simple(text.into())])
+            .collect::<Vec<_>>();
+        let results = crate::sync::block_on(model.generate_batch(inputs, 2));
+        assert_eq!(results.len(), 3);
+        assert!(results
+            .iter()
+            .all(|result| matches!(result, Err(crate::Error::Timeout))));
+        assert_eq!(crate::sync::block_on(model.stats()).calls, 3);
+    }
+
+    #[test]
+    fn test_model_set_backend() {
+        let model = Model::new(sllm::Backend::ChatGPT {
+            api_key: "old".into(),
+            model: "gpt-3.5-turbo".into(),
+        })
+        .unwrap();
+        let shared = model.clone();
+
+        crate::sync::block_on(async {
+            model
+                .set_backend(sllm::Backend::ChatGPT {
+                    api_key: "new".into(),
+                    model: "gpt-4".into(),
+                })
+                .await
+                .unwrap();
+            assert_eq!(shared.describe().await, "ChatGPT (gpt-4)");
+        });
+    }
+
+    #[test]
+    fn test_model_set_backend_during_retry() {
+        let mut model = Model::new(local_backend(vec![(503, "".into())])).unwrap();
+        model.set_retry_policy(Some(super::RetryPolicy {
+            max_retries: 1,
+            backoff: std::time::Duration::from_millis(200),
+        }));
+
+        let (result, swapped_at) = crate::sync::block_on(async {
+            futures::join!(
+                model.generate_response(vec![sllm::message::PromptMessage::new_simple(
+                    "Just Say only 'Hello'".into()
+                )]),
+                async {
+                    // lands inside the backoff after the first 503
+                    crate::sync::Timer::after(std::time::Duration::from_millis(50)).await;
+                    model
+                        .set_backend(local_backend(vec![(200, chat_response("Hello"))]))
+                        .await
+                        .unwrap();
+                    std::time::Instant::now()
+                }
+            )
+        });
+        let finished_at = std::time::Instant::now();
+
+        // the swap isn't held up by the pending retry, which then uses the new backend
+        assert_eq!(result.unwrap(), "Hello");
+        assert!(finished_at.duration_since(swapped_at) > std::time::Duration::from_millis(50));
+        let stats = crate::sync::block_on(model.stats());
+        assert_eq!(stats.retries, 1);
+        assert_eq!(stats.errors, 1);
+    }
+
+    use super::{ToKeywordString, ToPromptMessage};
+    use ai_agent_macro::{KeywordString, PromptContext};
+    use sllm::message::{MessageBuilder, PromptMessage};
 
     #[allow(dead_code)]
     #[derive(KeywordString)]
@@ -158,6 +523,29 @@ mod tests {
         prop: Vec<SubStruct>,
     }
 
+    #[allow(dead_code)]
+    #[derive(PromptContext)]
+    #[prompt_context(title = "Order")]
+    struct OrderContext {
+        order_id: u32,
+        status: String,
+        #[prompt_context(skip)]
+        internal_note: String,
+    }
+
+    #[test]
+    fn test_prompt_context() {
+        let order = OrderContext {
+            order_id: 42,
+            status: "shipped".into(),
+            internal_note: "hidden".into(),
+        };
+        assert_eq!(
+            order.to_prompt_message().build(),
+            "[Order]\norder_id: 42\nstatus: shipped"
+        );
+    }
+
     #[ignore]
     #[test]
     fn test_print_keyword() {
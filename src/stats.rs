@@ -0,0 +1,117 @@
+use std::{collections::VecDeque, time::Duration};
+
+use sllm::TokenUsage;
+
+// Latencies kept for the percentiles, oldest dropped first.
+const MAX_LATENCY_SAMPLES: usize = 1000;
+
+// Snapshot of the calls made through a `Model` since it was created.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelStats {
+    pub calls: u64,
+    pub errors: u64,
+    pub retries: u64,
+    // Token totals, from backends that report usage
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub p50_latency: Option<Duration>,
+    pub p95_latency: Option<Duration>,
+}
+
+impl ModelStats {
+    pub fn error_rate(&self) -> f64 {
+        if self.calls == 0 {
+            0.0
+        } else {
+            self.errors as f64 / self.calls as f64
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct StatsRecorder {
+    calls: u64,
+    errors: u64,
+    retries: u64,
+    prompt_tokens: u64,
+    completion_tokens: u64,
+    latencies: VecDeque<Duration>,
+}
+
+impl StatsRecorder {
+    pub(crate) fn record_call(&mut self, latency: Duration, is_ok: bool) {
+        self.calls += 1;
+        if !is_ok {
+            self.errors += 1;
+        }
+        if self.latencies.len() == MAX_LATENCY_SAMPLES {
+            self.latencies.pop_front();
+        }
+        self.latencies.push_back(latency);
+    }
+
+    pub(crate) fn record_usage(&mut self, usage: TokenUsage) {
+        self.prompt_tokens += usage.prompt_tokens as u64;
+        self.completion_tokens += usage.completion_tokens as u64;
+    }
+
+    pub(crate) fn record_retry(&mut self) {
+        self.retries += 1;
+    }
+
+    pub(crate) fn snapshot(&self) -> ModelStats {
+        let mut latencies = self.latencies.iter().copied().collect::<Vec<Duration>>();
+        latencies.sort();
+        let percentile =
+            |p: usize| (!latencies.is_empty()).then(|| latencies[(latencies.len() - 1) * p / 100]);
+
+        ModelStats {
+            calls: self.calls,
+            errors: self.errors,
+            retries: self.retries,
+            prompt_tokens: self.prompt_tokens,
+            completion_tokens: self.completion_tokens,
+            p50_latency: percentile(50),
+            p95_latency: percentile(95),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use sllm::TokenUsage;
+
+    use super::StatsRecorder;
+
+    #[test]
+    fn test_stats_recorder() {
+        let mut recorder = StatsRecorder::default();
+        assert_eq!(recorder.snapshot().p50_latency, None);
+        assert_eq!(recorder.snapshot().error_rate(), 0.0);
+
+        for ms in 1..=100 {
+            recorder.record_call(Duration::from_millis(ms), ms % 4 != 0);
+        }
+        recorder.record_retry();
+        recorder.record_usage(TokenUsage {
+            prompt_tokens: 12,
+            completion_tokens: 5,
+        });
+        recorder.record_usage(TokenUsage {
+            prompt_tokens: 8,
+            completion_tokens: 3,
+        });
+
+        let stats = recorder.snapshot();
+        assert_eq!(stats.calls, 100);
+        assert_eq!(stats.errors, 25);
+        assert_eq!(stats.retries, 1);
+        assert_eq!(stats.error_rate(), 0.25);
+        assert_eq!(stats.prompt_tokens, 20);
+        assert_eq!(stats.completion_tokens, 8);
+        assert_eq!(stats.p50_latency, Some(Duration::from_millis(50)));
+        assert_eq!(stats.p95_latency, Some(Duration::from_millis(95)));
+    }
+}
@@ -1,5 +1,7 @@
 //
 // TODO features
 pub use smol::block_on;
+pub use smol::future::or;
 pub use smol::lock::Mutex;
 pub use smol::lock::RwLock;
+pub use smol::Timer;
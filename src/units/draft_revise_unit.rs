@@ -0,0 +1,116 @@
+use sllm::message::PromptMessage;
+
+use crate::{Error, Model, ModuleParam, UnitProcess};
+
+const REVISE_INSTRUCTION: &str = "Above is a draft response to the request. Correct any mistakes and improve it where needed. Respond with only the final response.";
+
+// A cheaper model drafts the response and a stronger model revises it.
+#[derive(Debug, Clone)]
+pub struct DraftReviseUnit {
+    name: String,
+    draft_model: Model,
+    revise_model: Model,
+}
+
+impl DraftReviseUnit {
+    pub fn new(name: &str, draft_model: Model, revise_model: Model) -> Self {
+        Self {
+            name: name.into(),
+            draft_model,
+            revise_model,
+        }
+    }
+
+    fn construct_revise_param(groups: Vec<PromptMessage>, draft: &str) -> Vec<PromptMessage> {
+        let mut draft_group = PromptMessage::new_key_value("Draft");
+        draft_group.add_message("", draft);
+
+        let mut groups = groups;
+        groups.push(draft_group);
+        groups.push(REVISE_INSTRUCTION.into());
+        groups
+    }
+}
+
+#[async_trait::async_trait]
+impl UnitProcess for DraftReviseUnit {
+    fn get_name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    async fn process(&self, input: ModuleParam) -> Result<ModuleParam, Error> {
+        log::debug!("[{}] intput - {:?}", self.name, input);
+        let groups = match input {
+            ModuleParam::Str(req) => {
+                vec![PromptMessage::new_simple(req)]
+            }
+            ModuleParam::MessageBuilders(builder) => builder,
+            ModuleParam::None => {
+                vec![]
+            }
+        };
+
+        let draft = self.draft_model.generate_response(groups.clone()).await?;
+        log::debug!("[{}] draft - {}", self.name, draft);
+
+        // keep the draft if the revision fails
+        match self
+            .revise_model
+            .generate_response(Self::construct_revise_param(groups, &draft))
+            .await
+        {
+            Ok(revised) => Ok(revised.into()),
+            Err(e) => {
+                log::warn!("[{}] revision failed, using draft - {}", self.name, e);
+                Ok(draft.into())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sllm::message::PromptMessageBuilder;
+
+    use crate::{
+        prelude::*,
+        sync::block_on,
+        tests::{chat_response, local_backend, offline_model},
+        Model, UnitProcess,
+    };
+
+    use super::DraftReviseUnit;
+
+    #[test]
+    fn test_revise_param() {
+        let groups =
+            DraftReviseUnit::construct_revise_param(vec!["What is 2 + 2?".into()], "2 + 2 = 5");
+        assert_eq!(
+            PromptMessageBuilder::new(groups).build(),
+            format!(
+                "What is 2 + 2?\n\n[Draft]\n2 + 2 = 5\n\n{}",
+                super::REVISE_INSTRUCTION
+            )
+        );
+    }
+
+    #[test]
+    fn test_draft_failure() {
+        let draft_model = offline_model();
+        let revise_model = draft_model.clone();
+
+        let unit = DraftReviseUnit::new("draft_revise", draft_model, revise_model);
+        let result = block_on(unit.process("Hello".into()));
+        assert!(matches!(result, Err(crate::Error::Timeout)));
+    }
+
+    #[test]
+    fn test_revise_failure() {
+        let draft_model =
+            Model::new(local_backend(vec![(200, chat_response("2 + 2 = 4"))])).unwrap();
+
+        let unit = DraftReviseUnit::new("draft_revise", draft_model, offline_model());
+        let result = block_on(unit.process("What is 2 + 2?".into())).unwrap();
+        assert_eq!(result.as_string().unwrap(), "2 + 2 = 4");
+    }
+}
@@ -0,0 +1,186 @@
+use regex::{Captures, Regex};
+
+use crate::{Error, ModuleParam, UnitProcess};
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    // Leave the text as generated
+    #[default]
+    Markdown,
+    // Slack mrkdwn: `*bold*`, `_italic_`, `~strike~`, `<url|text>` links, headings as bold lines
+    SlackMarkdown,
+    // Strip markdown and cut to `max_length` characters (e.g. SMS)
+    PlainText {
+        max_length: Option<usize>,
+    },
+    // Escape for embedding in HTML
+    Html,
+}
+
+#[derive(Debug)]
+pub struct FormatterUnit {
+    name: String,
+    format: OutputFormat,
+    // `**bold**`/`__bold__` in groups 1-2, `*italic*`/`_italic_` in groups 3-4
+    emphasis: Regex,
+    strikethrough: Regex,
+    link: Regex,
+    heading: Regex,
+    code: Regex,
+    code_block: Regex,
+}
+
+impl FormatterUnit {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            format: OutputFormat::default(),
+            emphasis: Regex::new(
+                r"\*\*(.+?)\*\*|__(.+?)__|\*([^*\s](?:[^*\n]*[^*\s])?)\*|\b_([^_\s](?:[^_\n]*[^_\s])?)_\b",
+            )
+            .unwrap(),
+            strikethrough: Regex::new(r"~~(.+?)~~").unwrap(),
+            link: Regex::new(r"\[([^\]]+)\]\(([^)\s]+)\)").unwrap(),
+            heading: Regex::new(r"(?m)^#{1,6}\s+(.+)$").unwrap(),
+            code: Regex::new(r"`([^`\n]+)`").unwrap(),
+            code_block: Regex::new(r"(?s)```[^\n]*\n(.*?)```").unwrap(),
+        }
+    }
+
+    pub fn set_format(&mut self, format: OutputFormat) {
+        self.format = format;
+    }
+
+    pub fn format(&self, text: &str) -> String {
+        match &self.format {
+            OutputFormat::Markdown => text.to_string(),
+            OutputFormat::SlackMarkdown => {
+                // Slack treats these as control characters, so escape before adding links
+                let text = text
+                    .replace('&', "&amp;")
+                    .replace('<', "&lt;")
+                    .replace('>', "&gt;");
+                // bold and italics are matched together so `**x**` isn't read as `*x*`
+                let text = self.emphasis.replace_all(&text, |caps: &Captures| {
+                    match caps.get(1).or_else(|| caps.get(2)) {
+                        Some(bold) => format!("*{}*", bold.as_str()),
+                        None => format!(
+                            "_{}_",
+                            caps.get(3).or_else(|| caps.get(4)).unwrap().as_str()
+                        ),
+                    }
+                });
+                let text = self.strikethrough.replace_all(&text, "~$1~");
+                // a heading is already bold, nested `*` would break it
+                let text = self.heading.replace_all(&text, |caps: &Captures| {
+                    format!("*{}*", caps[1].replace('*', ""))
+                });
+                self.link.replace_all(&text, "<$2|$1>").into_owned()
+            }
+            OutputFormat::PlainText { max_length } => {
+                let text = self.code_block.replace_all(text, "$1");
+                let text = self.heading.replace_all(&text, "$1");
+                let text = self.emphasis.replace_all(&text, "$1$2$3$4");
+                let text = self.strikethrough.replace_all(&text, "$1");
+                let text = self.code.replace_all(&text, "$1");
+                let text = self.link.replace_all(&text, "$1 ($2)").into_owned();
+                match max_length {
+                    // no room even for the ellipsis
+                    Some(0) => String::new(),
+                    Some(max_length) if text.chars().count() > *max_length => {
+                        let mut truncated = text
+                            .chars()
+                            .take(max_length.saturating_sub(1))
+                            .collect::<String>();
+                        truncated.push('…');
+                        truncated
+                    }
+                    _ => text,
+                }
+            }
+            OutputFormat::Html => text
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('"', "&quot;")
+                .replace('\'', "&#39;"),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl UnitProcess for FormatterUnit {
+    fn get_name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    async fn process(&self, input: ModuleParam) -> Result<ModuleParam, Error> {
+        log::debug!("[{}] intput - {:?}", self.name, input);
+
+        // only the generated text is formatted, prompts pass through
+        match input {
+            ModuleParam::Str(text) => Ok(ModuleParam::Str(self.format(&text))),
+            other => Ok(other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FormatterUnit, OutputFormat};
+
+    #[test]
+    fn test_formatter_unit() {
+        let text =
+            "# Order\n**Shipped** via `DHL`, see [tracking](https://example.com/t?id=1) <now>";
+        let mut unit = FormatterUnit::new("formatter");
+        assert_eq!(unit.format(text), text);
+
+        unit.set_format(OutputFormat::SlackMarkdown);
+        assert_eq!(
+            unit.format(text),
+            "*Order*\n*Shipped* via `DHL`, see <https://example.com/t?id=1|tracking> &lt;now&gt;"
+        );
+        assert_eq!(
+            unit.format("## **Summary**\nTom & Jerry"),
+            "*Summary*\nTom &amp; Jerry"
+        );
+        let emphasis =
+            "this is *important*, __very__ _new_ and ~~old~~, not snake_case_name or 2 * 3 * 4";
+        assert_eq!(
+            unit.format(emphasis),
+            "this is _important_, *very* _new_ and ~old~, not snake_case_name or 2 * 3 * 4"
+        );
+
+        unit.set_format(OutputFormat::PlainText { max_length: None });
+        assert_eq!(
+            unit.format(text),
+            "Order\nShipped via DHL, see tracking (https://example.com/t?id=1) <now>"
+        );
+        assert_eq!(
+            unit.format(emphasis),
+            "this is important, very new and old, not snake_case_name or 2 * 3 * 4"
+        );
+
+        unit.set_format(OutputFormat::PlainText {
+            max_length: Some(10),
+        });
+        assert_eq!(unit.format(text), "Order\nShi…");
+        unit.set_format(OutputFormat::PlainText {
+            max_length: Some(0),
+        });
+        assert_eq!(unit.format(text), "");
+
+        unit.set_format(OutputFormat::PlainText { max_length: None });
+        assert_eq!(
+            unit.format("Run:\n```sh\ncargo run\n```\nthen `it`, not `a\nb`"),
+            "Run:\ncargo run\n\nthen it, not `a\nb`"
+        );
+
+        unit.set_format(OutputFormat::Html);
+        assert_eq!(
+            unit.format("<b>\"Tom & Jerry's\"</b>"),
+            "&lt;b&gt;&quot;Tom &amp; Jerry&#39;s&quot;&lt;/b&gt;"
+        );
+    }
+}
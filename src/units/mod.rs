@@ -1,7 +1,15 @@
 mod dialogue_unit;
+mod draft_revise_unit;
+mod formatter_unit;
 mod json_generator_unit;
 mod model_unit;
+mod redaction_unit;
+mod self_consistency_unit;
 
 pub use dialogue_unit::DialogueUnit;
+pub use draft_revise_unit::DraftReviseUnit;
+pub use formatter_unit::{FormatterUnit, OutputFormat};
 pub use json_generator_unit::JsonGeneratorUnit;
 pub use model_unit::ModelUnit;
+pub use redaction_unit::{PiiKind, RedactionStrategy, RedactionUnit};
+pub use self_consistency_unit::SelfConsistencyUnit;
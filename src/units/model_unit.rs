@@ -1,11 +1,12 @@
 use sllm::message::PromptMessage;
 
-use crate::{Error, Model, ModuleParam, UnitProcess};
+use crate::{Error, ErrorCategory, Model, ModuleParam, UnitProcess};
 
 #[derive(Debug, Clone)]
 pub struct ModelUnit {
     name: String,
     model: Model,
+    fallback_response: Option<String>,
 }
 
 impl ModelUnit {
@@ -13,8 +14,15 @@ impl ModelUnit {
         Self {
             name: name.into(),
             model,
+            fallback_response: None,
         }
     }
+
+    // Respond with this message instead of failing when the model can't be reached.
+    // Other errors, e.g. a bad template, are still returned.
+    pub fn set_fallback_response(&mut self, response: Option<&str>) {
+        self.fallback_response = response.map(String::from);
+    }
 }
 
 #[async_trait::async_trait]
@@ -38,10 +46,50 @@ impl UnitProcess for ModelUnit {
         };
 
         // generate the response
-        self.model
-            .generate_response(groups)
-            .await
-            .map(|result| result.into())
-            .map_err(|e| e.into())
+        match self.model.generate_response(groups).await {
+            Ok(result) => Ok(result.into()),
+            Err(e) => match &self.fallback_response {
+                Some(fallback) if e.category() == ErrorCategory::Provider => {
+                    log::warn!("[{}] model failed, using fallback - {}", self.name, e);
+                    Ok(fallback.clone().into())
+                }
+                _ => Err(e),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{sync::block_on, tests::offline_model, UnitProcess};
+
+    use super::ModelUnit;
+
+    #[test]
+    fn test_model_unit_fallback() {
+        let mut unit = ModelUnit::new("model", offline_model());
+        let result = block_on(unit.process("Hello".into()));
+        assert!(matches!(result, Err(crate::Error::Timeout)));
+
+        unit.set_fallback_response(Some("Sorry, please try again later."));
+        let result = block_on(unit.process("Hello".into())).unwrap();
+        assert_eq!(
+            result.as_string().unwrap(),
+            "Sorry, please try again later."
+        );
+
+        let mut msg = sllm::message::TemplatedMessage::new("Hello, {{ name }}!");
+        msg.declare_variable("name", sllm::message::VariableType::String, true);
+        let result = block_on(unit.process(vec![msg.into()].into()));
+        assert!(matches!(
+            result,
+            Err(crate::Error::SLLMError(
+                sllm::Error::TemplateVariableError { .. }
+            ))
+        ));
+
+        unit.set_fallback_response(None);
+        let result = block_on(unit.process("Hello".into()));
+        assert!(matches!(result, Err(crate::Error::Timeout)));
     }
 }
@@ -0,0 +1,237 @@
+use regex::{Captures, Regex};
+use sllm::message::{MessageBuilder, PromptMessage};
+
+use crate::{Error, ModuleParam, UnitProcess};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PiiKind {
+    Email,
+    Phone,
+    CreditCard,
+    NationalId,
+}
+
+impl PiiKind {
+    fn placeholder(&self) -> &'static str {
+        match self {
+            Self::Email => "[EMAIL]",
+            Self::Phone => "[PHONE]",
+            Self::CreditCard => "[CREDIT_CARD]",
+            Self::NationalId => "[NATIONAL_ID]",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RedactionStrategy {
+    // Replace with the kind label, e.g. `[EMAIL]`
+    #[default]
+    Placeholder,
+    // Replace every character with `*`, keeping the length
+    Mask,
+}
+
+#[derive(Debug)]
+pub struct RedactionUnit {
+    name: String,
+    strategy: RedactionStrategy,
+    kinds: Vec<PiiKind>,
+    // Ordered so that card numbers and national IDs are matched before phone numbers.
+    patterns: Vec<(PiiKind, Regex)>,
+}
+
+impl RedactionUnit {
+    pub fn new(name: &str) -> Self {
+        let patterns = vec![
+            (PiiKind::CreditCard, r"\b(?:\d[ -]?){12,18}\d\b"),
+            (PiiKind::NationalId, r"\b\d{3}-\d{2}-\d{4}\b"),
+            (
+                PiiKind::Email,
+                r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}",
+            ),
+            (
+                PiiKind::Phone,
+                // needs a country code, an area code in parentheses or the full
+                // 3-3-4 form, so plain digit groups like order numbers are left alone
+                r"(?:\+\d{1,3}[ .-]?\(?\d{1,4}\)?(?:[ .-]?\d{2,4}){2,3}|\(\d{3}\)[ .-]?\d{3}[ .-]?\d{4}|\b\d{3}[.-]\d{3}[.-]\d{4})\b",
+            ),
+        ]
+        .into_iter()
+        .map(|(kind, pattern)| (kind, Regex::new(pattern).unwrap()))
+        .collect();
+
+        Self {
+            name: name.into(),
+            strategy: RedactionStrategy::default(),
+            kinds: vec![
+                PiiKind::Email,
+                PiiKind::Phone,
+                PiiKind::CreditCard,
+                PiiKind::NationalId,
+            ],
+            patterns,
+        }
+    }
+
+    pub fn set_strategy(&mut self, strategy: RedactionStrategy) {
+        self.strategy = strategy;
+    }
+
+    pub fn set_kinds(&mut self, kinds: &[PiiKind]) {
+        self.kinds = kinds.to_vec();
+    }
+
+    // Redact the text, returning it with the kind of each redaction made.
+    pub fn redact(&self, text: &str) -> (String, Vec<PiiKind>) {
+        let mut redactions = Vec::new();
+        let mut result = text.to_string();
+
+        for (kind, regex) in self.patterns.iter() {
+            if !self.kinds.contains(kind) {
+                continue;
+            }
+            result = regex
+                .replace_all(&result, |caps: &Captures| {
+                    let matched = &caps[0];
+                    if *kind == PiiKind::CreditCard && !luhn_valid(matched) {
+                        return matched.to_string();
+                    }
+                    redactions.push(*kind);
+                    match self.strategy {
+                        RedactionStrategy::Placeholder => kind.placeholder().to_string(),
+                        RedactionStrategy::Mask => "*".repeat(matched.chars().count()),
+                    }
+                })
+                .into_owned();
+        }
+
+        (result, redactions)
+    }
+}
+
+fn luhn_valid(number: &str) -> bool {
+    let digits = number
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .collect::<Vec<u32>>();
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| {
+            if i % 2 == 1 {
+                let doubled = d * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                d
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+#[async_trait::async_trait]
+impl UnitProcess for RedactionUnit {
+    fn get_name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    async fn process(&self, input: ModuleParam) -> Result<ModuleParam, Error> {
+        // input can't be logged before it is redacted
+        let (output, redactions) = match input {
+            ModuleParam::Str(text) => {
+                let (redacted, redactions) = self.redact(&text);
+                (ModuleParam::Str(redacted), redactions)
+            }
+            ModuleParam::MessageBuilders(groups) => {
+                let mut redactions = Vec::new();
+                let groups = groups
+                    .into_iter()
+                    .map(|mut group| -> Result<PromptMessage, Error> {
+                        let (redacted, found) = self.redact(&group.try_build()?);
+                        redactions.extend(found);
+                        Ok(PromptMessage::new_simple(redacted))
+                    })
+                    .collect::<Result<Vec<PromptMessage>, Error>>()?;
+                (ModuleParam::MessageBuilders(groups), redactions)
+            }
+            ModuleParam::None => (ModuleParam::None, vec![]),
+        };
+
+        log::debug!("[{}] redactions - {:?}", self.name, redactions);
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sllm::message::{PromptMessage, TemplatedMessage};
+
+    use crate::{sync::block_on, UnitProcess};
+
+    use super::{PiiKind, RedactionStrategy, RedactionUnit};
+
+    #[test]
+    fn test_redaction_unit() {
+        let mut unit = RedactionUnit::new("redaction");
+        let (redacted, redactions) = unit.redact(
+            "Mail john.doe@example.com or call +1 555-123-4567. Card 4111 1111 1111 1111, SSN 123-45-6789.",
+        );
+        assert_eq!(
+            redacted,
+            "Mail [EMAIL] or call [PHONE]. Card [CREDIT_CARD], SSN [NATIONAL_ID]."
+        );
+        assert_eq!(
+            redactions,
+            vec![
+                PiiKind::CreditCard,
+                PiiKind::NationalId,
+                PiiKind::Email,
+                PiiKind::Phone
+            ]
+        );
+
+        // not a valid card number
+        let (redacted, _) = unit.redact("Order 12345678901234");
+        assert_eq!(redacted, "Order 12345678901234");
+
+        for phone in ["+1 555-123-4567", "(555) 123-4567", "555-123-4567"] {
+            let (redacted, redactions) = unit.redact(&format!("Call {}.", phone));
+            assert_eq!(redacted, "Call [PHONE].");
+            assert_eq!(redactions, vec![PiiKind::Phone]);
+        }
+
+        // digit groups without a phone shape are not phone numbers
+        for text in ["Order 1234 5678 9012", "2024 0115 1030"] {
+            let (redacted, redactions) = unit.redact(text);
+            assert_eq!(redacted, text);
+            assert!(redactions.is_empty());
+        }
+
+        unit.set_strategy(RedactionStrategy::Mask);
+        unit.set_kinds(&[PiiKind::Email]);
+        let result = block_on(async move {
+            unit.process("a@b.io, SSN 123-45-6789".into())
+                .await
+                .unwrap()
+        });
+        assert_eq!(result.as_string().unwrap(), "******, SSN 123-45-6789");
+    }
+
+    #[test]
+    fn test_redaction_template_error() {
+        let unit = RedactionUnit::new("redaction");
+        let input = vec![PromptMessage::new_templated(TemplatedMessage::new(
+            "Hi {{ name }}",
+        ))];
+        let result = block_on(unit.process(input.into()));
+        assert!(matches!(
+            result,
+            Err(crate::Error::SLLMError(sllm::Error::TeraError(_)))
+        ));
+    }
+}
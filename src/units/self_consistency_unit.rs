@@ -0,0 +1,161 @@
+use sllm::message::PromptMessage;
+
+use crate::{Error, Model, ModuleParam, UnitProcess};
+
+// Samples sent at once unless `set_concurrency` says otherwise.
+const DEFAULT_CONCURRENCY: usize = 4;
+
+// Samples the model `samples` times and answers with the most common response.
+// Needs a non-zero temperature, a deterministic model returns the same sample every time.
+#[derive(Debug, Clone)]
+pub struct SelfConsistencyUnit {
+    name: String,
+    model: Model,
+    samples: usize,
+    concurrency: usize,
+}
+
+impl SelfConsistencyUnit {
+    pub fn new(name: &str, model: Model, samples: usize) -> Self {
+        Self {
+            name: name.into(),
+            model,
+            samples: samples.max(1),
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+
+    // Limit how many samples are requested at once.
+    pub fn set_concurrency(&mut self, concurrency: usize) {
+        self.concurrency = concurrency.max(1);
+    }
+
+    fn normalize(response: &str) -> String {
+        response
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ")
+            .trim_end_matches('.')
+            .to_lowercase()
+    }
+
+    // The most common response, ignoring case, whitespace and a trailing period,
+    // and how many responses agree with it. Ties go to the earliest response.
+    pub fn vote(responses: &[String]) -> Option<(&str, usize)> {
+        let mut counts: Vec<(String, &str, usize)> = Vec::new();
+        for response in responses.iter() {
+            let key = Self::normalize(response);
+            match counts.iter_mut().find(|(k, _, _)| *k == key) {
+                Some((_, _, count)) => *count += 1,
+                None => counts.push((key, response.as_str(), 1)),
+            }
+        }
+
+        counts.into_iter().fold(
+            None,
+            |best: Option<(&str, usize)>, (_, response, count)| match best {
+                Some((_, best_count)) if best_count >= count => best,
+                _ => Some((response, count)),
+            },
+        )
+    }
+}
+
+#[async_trait::async_trait]
+impl UnitProcess for SelfConsistencyUnit {
+    fn get_name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    async fn process(&self, input: ModuleParam) -> Result<ModuleParam, Error> {
+        log::debug!("[{}] intput - {:?}", self.name, input);
+        let groups = match input {
+            ModuleParam::Str(req) => {
+                vec![PromptMessage::new_simple(req)]
+            }
+            ModuleParam::MessageBuilders(builder) => builder,
+            ModuleParam::None => {
+                vec![]
+            }
+        };
+
+        let mut responses = Vec::new();
+        let mut error = None;
+        for result in self
+            .model
+            .generate_batch(vec![groups; self.samples], self.concurrency)
+            .await
+        {
+            match result {
+                Ok(response) => responses.push(response),
+                Err(e) => {
+                    log::warn!("[{}] sample failed - {}", self.name, e);
+                    error.get_or_insert(e);
+                }
+            }
+        }
+
+        match Self::vote(&responses) {
+            Some((answer, count)) => {
+                log::debug!(
+                    "[{}] {}/{} samples agree, confidence {:.2}",
+                    self.name,
+                    count,
+                    self.samples,
+                    count as f64 / self.samples as f64
+                );
+                Ok(answer.to_string().into())
+            }
+            // every sample failed
+            None => Err(error.unwrap_or(Error::OutputIsEmpty)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        sync::block_on,
+        tests::{chat_response, local_backend, offline_model},
+        Model, UnitProcess,
+    };
+
+    use super::SelfConsistencyUnit;
+
+    #[test]
+    fn test_vote() {
+        let responses = ["42", "41", " 42.", "The answer is 42", "41", "42"]
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        assert_eq!(SelfConsistencyUnit::vote(&responses), Some(("42", 3)));
+
+        let responses = vec!["Paris".to_string(), "Rome".to_string()];
+        assert_eq!(SelfConsistencyUnit::vote(&responses), Some(("Paris", 1)));
+        assert_eq!(SelfConsistencyUnit::vote(&[]), None);
+    }
+
+    #[test]
+    fn test_all_samples_fail() {
+        let unit = SelfConsistencyUnit::new("self_consistency", offline_model(), 3);
+        let result = block_on(unit.process("What is 6 * 7?".into()));
+        assert!(matches!(result, Err(crate::Error::Timeout)));
+    }
+
+    #[test]
+    fn test_majority_answer() {
+        let model = Model::new(local_backend(vec![
+            (200, chat_response("42")),
+            (200, chat_response("41")),
+            (200, chat_response("42.")),
+        ]))
+        .unwrap();
+
+        let mut unit = SelfConsistencyUnit::new("self_consistency", model.clone(), 3);
+        unit.set_concurrency(2);
+        let result = block_on(unit.process("What is 6 * 7?".into())).unwrap();
+        // samples run concurrently, so either spelling of the majority may come first
+        assert_eq!(result.as_string().unwrap().trim_end_matches('.'), "42");
+        assert_eq!(block_on(model.stats()).calls, 3);
+    }
+}